@@ -14,68 +14,110 @@
 
 //! # Client support
 //!
-//! Support for connecting to JSONRPC servers over HTTP, sending requests,
-//! and parsing responses
+//! Support for connecting to JSONRPC servers, sending requests, and
+//! parsing responses
 //!
 
 use std::sync::{Arc, Mutex};
 
-use hyper::client::Client as HyperClient;
-use hyper::header::{Headers, Authorization, Basic};
-use hyper::status::StatusCode;
 use json;
 use json::value::Value as JsonValue;
+use serde;
 
 use super::{Request, Response};
 use error::Error;
+use transport::{HttpTransport, Transport};
 
-/// A handle to a remote JSONRPC server
-pub struct Client {
-    url: String,
-    user: Option<String>,
-    pass: Option<String>,
-    client: HyperClient,
+/// A handle to a remote JSONRPC server, generic over the [`Transport`] used
+/// to actually get bytes to and from it. Defaults to [`HttpTransport`] so
+/// existing callers using plain HTTP need not change anything.
+pub struct Client<T: Transport = HttpTransport> {
+    transport: T,
     nonce: Arc<Mutex<u64>>
 }
 
-impl Client {
-    /// Creates a new client
-    pub fn new(url: String, user: Option<String>, pass: Option<String>) -> Client {
-        // Check that if we have a password, we have a username; other way around is ok
-        debug_assert!(pass.is_none() || user.is_some());
+impl Client<HttpTransport> {
+    /// Creates a new client that talks to `url` over HTTP
+    pub fn new(url: String, user: Option<String>, pass: Option<String>) -> Client<HttpTransport> {
+        Client::with_transport(HttpTransport::new(url, user, pass))
+    }
+}
 
+impl<T: Transport> Client<T> {
+    /// Creates a new client using a caller-provided transport, e.g. a
+    /// persistent WebSocket connection or an in-process channel for testing.
+    ///
+    /// This takes only the `transport` itself, not the `url`/`user`/`pass`
+    /// triple that [`Client::new`] does: those are HTTP-specific connection
+    /// details, meaningless to a transport that isn't HTTP, so they live on
+    /// [`HttpTransport::new`](::transport::HttpTransport::new) instead of
+    /// here. A generic `Client` only ever needs to hand bytes to its
+    /// transport and get bytes back.
+    pub fn with_transport(transport: T) -> Client<T> {
         Client {
-            url: url,
-            user: user,
-            pass: pass,
-            client: HyperClient::new(),
+            transport,
             nonce: Arc::new(Mutex::new(0))
         }
     }
 
     /// Sends a request to a client
     pub fn send_request(&self, request: &Request) -> Result<Response, Error> {
-        // Build request
-        let request = json::to_string(&request).unwrap();
-
-        // Setup connection
-        let mut headers = Headers::new();
-        if let Some(ref user) = self.user {
-            headers.set(Authorization(Basic {
-                username: user.clone(),
-                password: self.pass.clone()
-            }));
+        let orig_id = request.id.clone();
+
+        let body = json::to_string(&request).unwrap();
+        let raw = self.transport.send_raw(body.as_bytes())?;
+
+        let response: Response = json::de::from_slice(&raw).map_err(Error::Json)?;
+        if let Some(ref expected) = orig_id {
+            if response.id != *expected {
+                return Err(Error::NonceMismatch { expected: expected.clone(), got: response.id.clone() });
+            }
+        }
+        Ok(response)
+    }
+
+    /// Sends a notification to a client. Notifications carry no `id` and the
+    /// server is not expected to reply to them, so this returns as soon as
+    /// the transport has delivered the request.
+    pub fn send_notification(&self, notification: &Request) -> Result<(), Error> {
+        let body = json::to_string(&notification).unwrap();
+        self.transport.send_raw(body.as_bytes())?;
+        Ok(())
+    }
+
+    /// Sends a batch of requests to a client as a single JSON array, and returns
+    /// the responses in the same order the requests were passed in.
+    ///
+    /// Per the JSON-RPC 2.0 spec the server is allowed to reply with the
+    /// responses out of order, so each `Response` is matched back to its
+    /// originating `Request` by `id` before being returned.
+    pub fn send_batch(&self, requests: &[Request]) -> Result<Vec<Response>, Error> {
+        let body = json::to_string(&requests).unwrap();
+        let raw = self.transport.send_raw(body.as_bytes())?;
+        if raw.is_empty() {
+            // A notification-only batch has no response at all
+            return Ok(vec![]);
         }
 
-        // Send request
-        let request = self.client.post(&self.url).headers(headers).body(&request);
-        let stream = try!(request.send().map_err(Error::Hyper));
-        if stream.status == StatusCode::Ok {
-            // TODO check nonces match
-            json::de::from_reader(stream).map_err(Error::Json)
-        } else {
-            Err(Error::BadStatus(stream.status))
+        let reply: JsonValue = json::de::from_slice(&raw).map_err(Error::Json)?;
+        if !reply.is_array() {
+            // The server collapsed the whole batch into a single error reply
+            let response: Response = json::from_value(reply).map_err(Error::Json)?;
+            let err = response.error.unwrap_or(JsonValue::Null);
+            return Err(Error::BatchError(json::from_value(err).map_err(Error::Json)?));
         }
+        let responses = json::from_value(reply).map_err(Error::Json)?;
+
+        reorder_responses(requests, responses)
+    }
+
+    /// Builds a request, sends it, and deserializes the `result` field of
+    /// the response into `R`. If the server returned a JSON-RPC error
+    /// object instead, it is returned as `Error::Rpc`.
+    pub fn call<R: serde::de::DeserializeOwned>(&self, name: String, params: Vec<JsonValue>) -> Result<R, Error> {
+        let request = self.build_request(name, params);
+        let response = self.send_request(&request)?;
+        response.into_result()
     }
 
     /// Builds a request
@@ -84,8 +126,18 @@ impl Client {
         *nonce += 1;
         Request {
             method: name,
-            params: params,
-            id: JsonValue::U64(*nonce)
+            params,
+            id: Some(JsonValue::from(*nonce))
+        }
+    }
+
+    /// Builds a notification: a request with no `id`, which the server
+    /// must not send a response to
+    pub fn build_notification(&self, name: String, params: Vec<JsonValue>) -> Request {
+        Request {
+            method: name,
+            params,
+            id: None
         }
     }
 
@@ -95,10 +147,38 @@ impl Client {
     }
 }
 
+/// Matches each response back to the request that produced it (by `id`) and
+/// returns them in the same order as `requests`.
+fn reorder_responses(requests: &[Request], mut responses: Vec<Response>) -> Result<Vec<Response>, Error> {
+    let mut ordered = Vec::with_capacity(requests.len());
+    for request in requests {
+        // Notifications within a batch produce no response to match against
+        let id = match request.id {
+            Some(ref id) => id,
+            None => continue,
+        };
+        let pos = responses.iter().position(|resp| resp.id == *id);
+        match pos {
+            Some(pos) => ordered.push(responses.remove(pos)),
+            None => return Err(Error::MissingResponse(id.clone())),
+        }
+    }
+    Ok(ordered)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A transport that just hands back a canned reply
+    struct MockTransport(Vec<u8>);
+
+    impl Transport for MockTransport {
+        fn send_raw(&self, _body: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
     #[test]
     fn sanity() {
         let client = Client::new("localhost".to_owned(), None, None);
@@ -109,5 +189,107 @@ mod tests {
         assert_eq!(client.last_nonce(), 2);
         assert!(req1 != req2);
     }
-}
 
+    #[test]
+    fn notifications_serialize_without_an_id_field() {
+        let client = Client::new("localhost".to_owned(), None, None);
+        let notification = client.build_notification("ping".to_owned(), vec![]);
+        assert!(notification.id.is_none());
+
+        let serialized = json::to_string(&notification).unwrap();
+        assert!(!serialized.contains("\"id\""));
+    }
+
+    #[test]
+    fn reorder_responses_matches_by_id_regardless_of_server_order() {
+        let req1 = Request { method: "a".to_owned(), params: vec![], id: Some(JsonValue::from(1)) };
+        let req2 = Request { method: "b".to_owned(), params: vec![], id: Some(JsonValue::from(2)) };
+        let resp1 = Response { result: Some(JsonValue::from(1)), error: None, id: JsonValue::from(1) };
+        let resp2 = Response { result: Some(JsonValue::from(2)), error: None, id: JsonValue::from(2) };
+
+        // server replied with resp2 before resp1
+        let ordered = reorder_responses(&[req1, req2], vec![resp2.clone(), resp1.clone()]).unwrap();
+        assert_eq!(ordered, vec![resp1, resp2]);
+    }
+
+    #[test]
+    fn send_request_detects_nonce_mismatch() {
+        let reply = br#"{"result":1,"error":null,"id":999}"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+        let request = client.build_request("ping".to_owned(), vec![]);
+
+        match client.send_request(&request) {
+            Err(Error::NonceMismatch { expected, got }) => {
+                assert_eq!(expected, JsonValue::from(1));
+                assert_eq!(got, JsonValue::from(999));
+            }
+            other => panic!("expected Error::NonceMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn send_batch_of_only_notifications_yields_no_responses() {
+        let client = Client::with_transport(MockTransport(vec![]));
+        let notification = client.build_notification("ping".to_owned(), vec![]);
+        let responses = client.send_batch(&[notification]).unwrap();
+        assert!(responses.is_empty());
+    }
+
+    #[test]
+    fn send_batch_reports_a_collapsed_error_reply() {
+        let reply = br#"{"result":null,"error":{"code":-32600,"message":"Invalid Request","data":null},"id":null}"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+        let request = client.build_request("ping".to_owned(), vec![]);
+
+        match client.send_batch(&[request]) {
+            Err(Error::BatchError(ref e)) => assert_eq!(e.code, -32600),
+            other => panic!("expected Error::BatchError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn send_batch_reorders_out_of_order_server_replies() {
+        let reply = br#"[{"result":2,"error":null,"id":2},{"result":1,"error":null,"id":1}]"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+        let req1 = client.build_request("a".to_owned(), vec![]);
+        let req2 = client.build_request("b".to_owned(), vec![]);
+
+        let responses = client.send_batch(&[req1, req2]).unwrap();
+        assert_eq!(responses[0].result, Some(JsonValue::from(1)));
+        assert_eq!(responses[1].result, Some(JsonValue::from(2)));
+    }
+
+    #[test]
+    fn call_deserializes_the_result_into_the_requested_type() {
+        let reply = br#"{"result":42,"error":null,"id":1}"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+
+        let result: u64 = client.call("ping".to_owned(), vec![]).unwrap();
+        assert_eq!(result, 42);
+    }
+
+    #[test]
+    fn call_turns_a_server_error_into_error_rpc() {
+        let reply = br#"{"result":null,"error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+
+        match client.call::<u64>("ping".to_owned(), vec![]) {
+            Err(Error::Rpc(ref e)) => {
+                assert_eq!(e.code, -32602);
+                assert_eq!(e.message, "Invalid params");
+            }
+            other => panic!("expected Error::Rpc, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn call_reports_a_result_that_does_not_fit_the_requested_type() {
+        let reply = br#"{"result":"not a number","error":null,"id":1}"#;
+        let client = Client::with_transport(MockTransport(reply.to_vec()));
+
+        match client.call::<u64>("ping".to_owned(), vec![]) {
+            Err(Error::Json(_)) => {}
+            other => panic!("expected Error::Json, got {:?}", other),
+        }
+    }
+}
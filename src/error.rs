@@ -0,0 +1,86 @@
+// Rust JSON-RPC Library
+// Written in 2015 by
+//     Andrew Poelstra <apoelstra@wpsoftware.net>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! # Error handling
+//!
+//! Some useful methods for creating Error objects
+//!
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use hyper;
+use hyper::status::StatusCode;
+use json;
+use json::value::Value as JsonValue;
+
+/// The JSON-RPC error object, as returned in the `error` field of a `Response`
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RpcError {
+    /// The integer error code
+    pub code: i64,
+    /// A short description of the error
+    pub message: String,
+    /// Additional information, if any
+    pub data: Option<JsonValue>
+}
+
+/// A library error
+#[derive(Debug)]
+pub enum Error {
+    /// Json error
+    Json(json::Error),
+    /// IO error reading from a connection
+    Io(io::Error),
+    /// Hyper error
+    Hyper(hyper::Error),
+    /// Client error: non-`2xx` status code from server
+    BadStatus(StatusCode),
+    /// A batch reply did not contain a response matching one of the requests
+    MissingResponse(JsonValue),
+    /// The server collapsed a batch request into a single error object
+    BatchError(RpcError),
+    /// The `id` on a response did not match the `id` of the request that was sent
+    NonceMismatch {
+        /// The `id` of the request that was sent
+        expected: JsonValue,
+        /// The `id` on the response that was received
+        got: JsonValue
+    },
+    /// The server returned a JSON-RPC error object instead of a result
+    Rpc(RpcError)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Json(ref e) => write!(f, "JSON decode error: {}", e),
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::Hyper(ref e) => write!(f, "Hyper error: {}", e),
+            Error::BadStatus(ref s) => write!(f, "Bad response status from server: {}", s),
+            Error::MissingResponse(ref id) => write!(f, "batch reply missing response for request id {}", id),
+            Error::BatchError(ref e) => write!(f, "batch request failed: {} (code {})", e.message, e.code),
+            Error::NonceMismatch { ref expected, ref got } =>
+                write!(f, "nonce mismatch: expected response id {}, got {}", expected, got),
+            Error::Rpc(ref e) => write!(f, "JSON-RPC error {}: {}", e.code, e.message)
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        "JSONRPC error"
+    }
+}
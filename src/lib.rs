@@ -0,0 +1,71 @@
+// Rust JSON-RPC Library
+// Written in 2015 by
+//     Andrew Poelstra <apoelstra@wpsoftware.net>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! # Rust JSON-RPC
+//!
+//! Rust support for the JSON-RPC 2.0 protocol
+//!
+
+#![crate_name = "jsonrpc"]
+
+#[macro_use] extern crate serde_derive;
+extern crate serde;
+extern crate serde_json as json;
+extern crate hyper;
+
+pub mod client;
+pub mod error;
+pub mod server;
+pub mod transport;
+
+use json::value::Value as JsonValue;
+
+/// A JSONRPC request object
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Request {
+    /// The name of the RPC call
+    pub method: String,
+    /// Parameters to the RPC call
+    pub params: Vec<JsonValue>,
+    /// Identifier for this Request, which should appear in the response.
+    /// Omitted for notifications, which the server must not reply to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<JsonValue>
+}
+
+/// A JSONRPC response object
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Response {
+    /// A result if there is one, or null
+    pub result: Option<JsonValue>,
+    /// An error if there is one, or null
+    pub error: Option<JsonValue>,
+    /// Identifier for this Response, which should match that of the request
+    pub id: JsonValue
+}
+
+impl Response {
+    /// Extracts the result from a response, consuming it in the process.
+    /// Returns the JSON-RPC error object as a structured `Error::Rpc` if the
+    /// server returned one instead of a result.
+    pub fn into_result<T: serde::de::DeserializeOwned>(self) -> Result<T, error::Error> {
+        if let Some(err) = self.error {
+            let rpc_err = json::from_value(err).map_err(error::Error::Json)?;
+            return Err(error::Error::Rpc(rpc_err));
+        }
+
+        let result = self.result.unwrap_or(JsonValue::Null);
+        json::from_value(result).map_err(error::Error::Json)
+    }
+}
@@ -0,0 +1,153 @@
+// Rust JSON-RPC Library
+// Written in 2015 by
+//     Andrew Poelstra <apoelstra@wpsoftware.net>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! # Server support
+//!
+//! A minimal method dispatcher for answering JSONRPC requests, complementing
+//! the client half of this crate
+//!
+
+use std::collections::HashMap;
+
+use json;
+use json::value::Value as JsonValue;
+
+use error::RpcError;
+use super::{Request, Response};
+
+/// A handler for a single RPC method: takes the call's params and returns
+/// either a JSON result or a JSON-RPC error object. Handlers that reject
+/// their params should use code `-32602` ("Invalid params"), matching the
+/// `-32601` ("Method not found") that `Dispatcher::handle` generates itself
+/// for unregistered methods.
+pub type Handler = Box<dyn Fn(Vec<JsonValue>) -> Result<JsonValue, RpcError>>;
+
+/// Dispatches incoming `Request`s to registered method handlers and turns
+/// their output into `Response`s
+pub struct Dispatcher {
+    handlers: HashMap<String, Handler>
+}
+
+impl Dispatcher {
+    /// Creates a dispatcher with no registered methods
+    pub fn new() -> Dispatcher {
+        Dispatcher { handlers: HashMap::new() }
+    }
+
+    /// Registers a handler for `method`, replacing any handler already
+    /// registered for it
+    pub fn register(&mut self, method: &str, handler: Handler) {
+        self.handlers.insert(method.to_owned(), handler);
+    }
+
+    /// Looks up the handler for `request.method`, invokes it, and wraps the
+    /// outcome in a `Response`. Per the JSON-RPC 2.0 spec the server must
+    /// not reply to a notification, so this returns `None` when `request.id`
+    /// is `None` instead of manufacturing a `Response` with a null id.
+    pub fn handle(&self, request: &Request) -> Option<Response> {
+        let id = match request.id {
+            Some(ref id) => id.clone(),
+            None => return None,
+        };
+        let response = match self.handlers.get(&request.method) {
+            Some(handler) => rpc_response(id, handler(request.params.clone())),
+            None => rpc_response(id, Err(RpcError {
+                code: -32601,
+                message: "Method not found".to_owned(),
+                data: None
+            }))
+        };
+        Some(response)
+    }
+
+    /// Runs `handle` over a whole batch of requests, dropping the
+    /// notifications (which get no reply), mirroring the client's
+    /// `send_batch` treatment of a notification-only batch as empty.
+    pub fn handle_batch(&self, requests: &[Request]) -> Vec<Response> {
+        requests.iter().filter_map(|request| self.handle(request)).collect()
+    }
+}
+
+impl Default for Dispatcher {
+    fn default() -> Dispatcher {
+        Dispatcher::new()
+    }
+}
+
+fn rpc_response(id: JsonValue, result: Result<JsonValue, RpcError>) -> Response {
+    match result {
+        Ok(value) => Response { result: Some(value), error: None, id },
+        Err(err) => Response { result: None, error: Some(json::to_value(&err).unwrap()), id }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo_dispatcher() -> Dispatcher {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register("echo", Box::new(|params| Ok(JsonValue::Array(params))));
+        dispatcher
+    }
+
+    #[test]
+    fn handle_invokes_the_registered_method() {
+        let dispatcher = echo_dispatcher();
+        let request = Request { method: "echo".to_owned(), params: vec![JsonValue::from(1)], id: Some(JsonValue::from(7)) };
+
+        let response = dispatcher.handle(&request).unwrap();
+        assert_eq!(response.id, JsonValue::from(7));
+        assert_eq!(response.result, Some(JsonValue::Array(vec![JsonValue::from(1)])));
+        assert!(response.error.is_none());
+    }
+
+    #[test]
+    fn handle_reports_method_not_found() {
+        let dispatcher = echo_dispatcher();
+        let request = Request { method: "nonexistent".to_owned(), params: vec![], id: Some(JsonValue::from(1)) };
+
+        let response = dispatcher.handle(&request).unwrap();
+        assert!(response.result.is_none());
+        let error: RpcError = json::from_value(response.error.unwrap()).unwrap();
+        assert_eq!(error.code, -32601);
+    }
+
+    #[test]
+    fn handle_does_not_reply_to_notifications() {
+        let dispatcher = echo_dispatcher();
+        let notification = Request { method: "echo".to_owned(), params: vec![], id: None };
+
+        assert!(dispatcher.handle(&notification).is_none());
+    }
+
+    #[test]
+    fn handle_batch_drops_notifications_but_keeps_calls() {
+        let dispatcher = echo_dispatcher();
+        let call = Request { method: "echo".to_owned(), params: vec![], id: Some(JsonValue::from(1)) };
+        let notification = Request { method: "echo".to_owned(), params: vec![], id: None };
+
+        let responses = dispatcher.handle_batch(&[notification.clone(), call, notification]);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].id, JsonValue::from(1));
+    }
+
+    #[test]
+    fn handle_batch_of_only_notifications_yields_no_responses() {
+        let dispatcher = echo_dispatcher();
+        let notification = Request { method: "echo".to_owned(), params: vec![], id: None };
+
+        assert!(dispatcher.handle_batch(&[notification]).is_empty());
+    }
+}
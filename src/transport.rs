@@ -0,0 +1,115 @@
+// Rust JSON-RPC Library
+// Written in 2015 by
+//     Andrew Poelstra <apoelstra@wpsoftware.net>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! # Transports
+//!
+//! The wire-level mechanics of getting a serialized request to a server and
+//! a serialized reply back. [`Client`](super::client::Client) is generic over
+//! any [`Transport`], so callers who need something other than plain HTTP
+//! (a persistent WebSocket connection, raw TCP, an in-process channel for
+//! tests, ...) can plug one in without touching request-building or nonce
+//! tracking.
+//!
+//! Note that `send_raw` is synchronous, matching the rest of this crate;
+//! an async `Transport` variant is left for a follow-up once the crate has
+//! an async HTTP client to build it on.
+//!
+
+use std::io::Read;
+
+use hyper::client::Client as HyperClient;
+use hyper::header::{Headers, Authorization, Basic};
+
+use error::Error;
+
+/// A mechanism for delivering a serialized JSONRPC request to a server and
+/// getting its serialized reply back
+pub trait Transport {
+    /// Sends `body` to the server and returns its raw reply. An empty
+    /// `Vec` indicates that the server sent no body at all (as for a
+    /// notification or a notification-only batch).
+    fn send_raw(&self, body: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// The original transport used by this crate: a single HTTP POST per
+/// request, optionally with HTTP basic auth, over a `hyper` client
+pub struct HttpTransport {
+    url: String,
+    user: Option<String>,
+    pass: Option<String>,
+    client: HyperClient
+}
+
+impl HttpTransport {
+    /// Creates a new HTTP transport pointed at `url`
+    pub fn new(url: String, user: Option<String>, pass: Option<String>) -> HttpTransport {
+        // Check that if we have a password, we have a username; other way around is ok
+        debug_assert!(pass.is_none() || user.is_some());
+
+        HttpTransport {
+            url,
+            user,
+            pass,
+            client: HyperClient::new()
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send_raw(&self, body: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut headers = Headers::new();
+        if let Some(ref user) = self.user {
+            headers.set(Authorization(Basic {
+                username: user.clone(),
+                password: self.pass.clone()
+            }));
+        }
+
+        let request = self.client.post(&self.url).headers(headers).body(body);
+        let mut stream = request.send().map_err(Error::Hyper)?;
+        if !stream.status.is_success() {
+            return Err(Error::BadStatus(stream.status));
+        }
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(Error::Io)?;
+        Ok(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::Client;
+
+    /// A transport that just hands back a canned reply, demonstrating that
+    /// `Client` works over something other than `HttpTransport`
+    struct MockTransport(Vec<u8>);
+
+    impl Transport for MockTransport {
+        fn send_raw(&self, _body: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn client_works_over_a_custom_transport() {
+        let reply = br#"{"result":42,"error":null,"id":1}"#.to_vec();
+        let client = Client::with_transport(MockTransport(reply));
+
+        let request = client.build_request("ping".to_owned(), vec![]);
+        let response = client.send_request(&request).unwrap();
+        assert_eq!(response.result, Some(42.into()));
+    }
+}